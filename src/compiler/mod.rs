@@ -5,8 +5,11 @@ module implements the YARA compiler.
 */
 use std::cell::RefCell;
 use std::fmt;
+use std::io;
+use std::io::{Read, Write};
 use std::path::Path;
 use std::rc::Rc;
+use serde::{Deserialize, Serialize};
 use string_interner::symbol::SymbolU32;
 use string_interner::{DefaultBackend, StringInterner};
 use walrus::ir::{InstrSeqId, UnaryOp};
@@ -37,8 +40,30 @@ mod tests;
 pub struct Compiler {
     colorize_errors: bool,
 
+    /// Custom wasmtime configuration used for compiling the generated
+    /// WebAssembly module, set via [`Compiler::engine_config`] or
+    /// [`Compiler::opt_level`]. When `None`, `build()` uses the shared
+    /// [`crate::wasm::ENGINE`] and its default settings, exactly like
+    /// before this option existed.
+    engine_config: Option<wasmtime::Config>,
+
+    /// Limits for wasmtime's pooling instance allocator, set via
+    /// [`Compiler::pooling_limits`]. `None` means scans keep using
+    /// wasmtime's on-demand allocator, which is the default today: every
+    /// scan mmaps its own linear memory and tables and frees them when the
+    /// scan finishes.
+    pooling_limits: Option<PoolingLimits>,
+
     report_builder: ReportBuilder,
-    sym_tbl: SymbolTable,
+
+    /// Namespaces created so far. Rules and imports are always added to
+    /// whichever namespace is current, see [`Compiler::new_namespace`] and
+    /// [`Compiler::current_namespace_idx`].
+    namespaces: Vec<Namespace>,
+
+    /// Index, within `namespaces`, of the namespace that subsequent calls
+    /// to [`Compiler::add_source`] add rules and imports to.
+    current_namespace_idx: usize,
 
     /// Pool that contains all the identifiers used in the rules. Each
     /// identifier appears only once, even if they are used by multiple
@@ -64,20 +89,54 @@ pub struct Compiler {
 }
 
 impl Compiler {
+    /// Name given to the namespace that's active by default, before
+    /// [`Compiler::new_namespace`] is ever called.
+    const DEFAULT_NAMESPACE: &'static str = "default";
+
     /// Creates a new YARA compiler.
     pub fn new() -> Self {
+        let mut ident_pool = StringInterner::default();
+        let default_namespace = Namespace {
+            ident: ident_pool.get_or_intern(Self::DEFAULT_NAMESPACE),
+            sym_tbl: SymbolTable::new(),
+        };
         Self {
             colorize_errors: false,
+            engine_config: None,
+            pooling_limits: None,
             warnings: Vec::new(),
             rules: Vec::new(),
             patterns: Vec::new(),
             report_builder: ReportBuilder::new(),
-            ident_pool: StringInterner::default(),
+            ident_pool,
             wasm_mod: wasm::ModuleBuilder::new(),
-            sym_tbl: SymbolTable::new(),
+            namespaces: vec![default_namespace],
+            current_namespace_idx: 0,
         }
     }
 
+    /// Starts a new namespace, so that rules added by subsequent calls to
+    /// [`Compiler::add_source`] don't collide with, and can't see the
+    /// imports of, rules added so far.
+    ///
+    /// This mirrors classic YARA's per-file namespaces
+    /// (`yr_compiler_add_file` and friends take a namespace name): large
+    /// rule collections assembled from several vendors can declare a rule
+    /// with the same identifier in two different namespaces without it
+    /// being treated as a duplicate, and a `import` in one namespace
+    /// doesn't leak its symbols into another.
+    ///
+    /// Namespace names don't need to be unique; calling this method always
+    /// starts a brand new, empty namespace, even if `name` was used before.
+    pub fn new_namespace(mut self, name: &str) -> Self {
+        self.namespaces.push(Namespace {
+            ident: self.ident_pool.get_or_intern(name),
+            sym_tbl: SymbolTable::new(),
+        });
+        self.current_namespace_idx = self.namespaces.len() - 1;
+        self
+    }
+
     /// Specifies whether the compiler should produce colorful error messages.
     ///
     /// Colorized error messages contain ANSI escape sequences that make them
@@ -87,6 +146,78 @@ impl Compiler {
         self
     }
 
+    /// Sets the wasmtime [`wasmtime::Config`] used for compiling the
+    /// generated WebAssembly module.
+    ///
+    /// By default all [`Compiler`] instances share a single, lazily
+    /// initialized `Engine` (`crate::wasm::ENGINE`) configured with
+    /// wasmtime's defaults. Calling this method makes `build()` create a
+    /// dedicated `Engine` from `cfg` instead, which lets callers trade
+    /// Cranelift compile time for scan-time performance, enable or disable
+    /// the on-disk native code cache, or toggle parallel compilation. See
+    /// [`Compiler::opt_level`] for ready-made presets covering the common
+    /// cases.
+    ///
+    /// Rules built with a custom engine configuration can still be passed
+    /// to [`CompiledRules::serialize`], but [`CompiledRules::deserialize`]
+    /// always loads them back through the shared `crate::wasm::ENGINE`, so
+    /// the configuration must be compatible with that engine's defaults
+    /// for the round-trip to produce a working module.
+    pub fn engine_config(mut self, cfg: wasmtime::Config) -> Self {
+        self.engine_config = Some(cfg);
+        self
+    }
+
+    /// Selects one of the built-in Cranelift optimization presets.
+    ///
+    /// This is a convenience wrapper around [`Compiler::engine_config`] for
+    /// the two tradeoffs most callers care about: compiling rules
+    /// interactively as they're edited ([`OptLevel::FastCompile`]) versus
+    /// compiling once and scanning for a long time, e.g. in a daemon
+    /// ([`OptLevel::FastScan`]).
+    pub fn opt_level(mut self, opt_level: OptLevel) -> Self {
+        let mut cfg = wasmtime::Config::new();
+        cfg.parallel_compilation(true);
+        // `cache_config_load_default` fails when the user has a malformed
+        // or unreadable wasmtime cache config file (e.g. bad TOML, wrong
+        // permissions). That's intentionally not fatal here: caching is a
+        // best-effort speedup, not something either preset promises, so a
+        // broken cache config degrades to "no cache" rather than failing
+        // `opt_level` itself. It does mean the speedup from caching can
+        // silently not kick in; there's no `Warning` constructor available
+        // for this in the current `warnings` module to surface it instead.
+        match opt_level {
+            OptLevel::FastCompile => {
+                cfg.cranelift_opt_level(wasmtime::OptLevel::None);
+                cfg.cache_config_load_default().ok();
+            }
+            OptLevel::FastScan => {
+                cfg.cranelift_opt_level(wasmtime::OptLevel::Speed);
+                cfg.cache_config_load_default().ok();
+            }
+        }
+        self.engine_config = Some(cfg);
+        self
+    }
+
+    /// Makes scans performed with the resulting [`CompiledRules`] use
+    /// wasmtime's pooling instance allocator, with the given `limits`.
+    ///
+    /// The on-demand allocator used by default mmaps a fresh linear memory
+    /// and table for every scanned input, which is fine for scanning a
+    /// handful of files but becomes the bottleneck when scanning very many
+    /// small ones, e.g. a directory with 100k files: the pooling allocator
+    /// pre-reserves `limits` worth of memory/table slots once, up front,
+    /// and hands them out and reclaims them on every scan instead.
+    ///
+    /// If a rule set needs more memory or table elements than `limits`
+    /// allows, [`Compiler::build`] returns an error instead of the first
+    /// scan trapping.
+    pub fn pooling_limits(mut self, limits: PoolingLimits) -> Self {
+        self.pooling_limits = Some(limits);
+        self
+    }
+
     /// Adds a YARA source code to be compiled.
     ///
     /// This function can be called multiple times.
@@ -135,18 +266,38 @@ impl Compiler {
                     Vec::new()
                 };
 
+                let namespace_id = self.current_namespace_idx as NamespaceId;
+                let ident_id = self
+                    .ident_pool
+                    .get_or_intern(rule.identifier.as_str());
+
+                // The same rule identifier can be reused in different
+                // namespaces, but not twice within the same namespace.
+                if self.rules.iter().any(|r| {
+                    r.namespace == namespace_id && r.ident == ident_id
+                }) {
+                    return Err(Error::CompileError(
+                        CompileError::duplicate_rule(
+                            &self.report_builder,
+                            &src,
+                            rule.identifier.as_str().to_string(),
+                            rule.identifier.span(),
+                        ),
+                    ));
+                }
+
                 let rule_id = self.rules.len() as RuleId;
 
                 self.rules.push(CompiledRule {
-                    ident: self
-                        .ident_pool
-                        .get_or_intern(rule.identifier.as_str()),
+                    ident: ident_id,
+                    namespace: namespace_id,
                     patterns: pairs,
                 });
 
                 let mut ctx = Context {
                     src: &src,
-                    root_sym_tbl: &self.sym_tbl,
+                    root_sym_tbl: &self.namespaces[self.current_namespace_idx]
+                        .sym_tbl,
                     current_struct: None,
                     ident_pool: &self.ident_pool,
                     report_builder: &self.report_builder,
@@ -199,8 +350,13 @@ impl Compiler {
                     block.unop(UnaryOp::I32Eqz);
                     block.br_if(block.id());
 
-                    // The RuleID is the argument to `rule_match`.
+                    // `rule_match` takes the RuleID and the NamespaceID of
+                    // the matching rule, so that whoever reports scan
+                    // results downstream doesn't have to go back to
+                    // `CompiledRules::rules()` out of band to tell which
+                    // namespace a match came from.
                     block.i32_const(rule_id as i32);
+                    block.i32_const(namespace_id as i32);
 
                     // Emit call instruction for calling `rule_match`.
                     block.call(ctx.borrow().wasm_symbols.rule_match);
@@ -215,19 +371,62 @@ impl Compiler {
         // Finish building the WebAssembly module.
         let mut wasm_mod = self.wasm_mod.build();
 
+        // If the caller asked for a bounded pool, make sure these rules
+        // actually fit in it. Without this check a rule set that needs more
+        // memory or table slots than the pool provides would only fail the
+        // first time it's scanned, as a wasmtime trap rather than a clean
+        // error.
+        if let Some(limits) = &self.pooling_limits {
+            Self::check_pooling_limits(&wasm_mod, limits)?;
+        }
+
+        // Use a dedicated engine when the caller configured one via
+        // `engine_config`/`opt_level`/`pooling_limits`, otherwise fall back
+        // to the shared `ENGINE` so compilers that don't care about this
+        // keep reusing the same cached engine as before.
+        let engine = if self.engine_config.is_some()
+            || self.pooling_limits.is_some()
+        {
+            let mut cfg = self.engine_config.unwrap_or_default();
+
+            if let Some(limits) = &self.pooling_limits {
+                let mut pooling = wasmtime::PoolingAllocationConfig::default();
+                pooling.total_core_instances(limits.max_instances);
+                pooling.total_memories(limits.max_instances);
+                pooling.total_tables(limits.max_instances);
+                pooling.max_memory_size(
+                    limits.max_memory_pages as usize * 64 * 1024,
+                );
+                pooling.table_elements(limits.max_table_elements);
+                cfg.allocation_strategy(
+                    wasmtime::InstanceAllocationStrategy::Pooling(pooling),
+                );
+            }
+
+            wasmtime::Engine::new(&cfg).map_err(|err| {
+                Error::Io(io::Error::new(
+                    io::ErrorKind::Other,
+                    format!("invalid wasmtime engine configuration: {}", err),
+                ))
+            })?
+        } else {
+            crate::wasm::ENGINE.clone()
+        };
+
         // Compile the WebAssembly module for the current platform. This
         // panics if the WebAssembly code is somehow invalid, which should
         // not happen, as the code is generated by YARA itself.
         let compiled_wasm_mod = wasmtime::Module::from_binary(
-            &crate::wasm::ENGINE,
+            &engine,
             wasm_mod.emit_wasm().as_slice(),
         )
         .unwrap();
 
         Ok(CompiledRules {
             compiled_wasm_mod,
-            wasm_mod,
+            wasm_mod: Some(wasm_mod),
             ident_pool: self.ident_pool,
+            namespaces: self.namespaces.iter().map(|ns| ns.ident).collect(),
             patterns: Vec::new(),
             rules: self.rules,
         })
@@ -251,6 +450,43 @@ impl Compiler {
 }
 
 impl Compiler {
+    /// Checks that the generated module's memory and table requirements fit
+    /// within `limits`, returning a clear [`Error`] instead of letting a
+    /// too-small pool turn into a wasmtime trap the first time the rules
+    /// are scanned.
+    fn check_pooling_limits(
+        wasm_mod: &Module,
+        limits: &PoolingLimits,
+    ) -> Result<(), Error> {
+        for memory in wasm_mod.memories.iter() {
+            if memory.initial > limits.max_memory_pages as u64 {
+                return Err(Error::Io(io::Error::new(
+                    io::ErrorKind::Other,
+                    format!(
+                        "rules require at least {} wasm page(s) of memory, \
+                         but pooling_limits allows only {}",
+                        memory.initial, limits.max_memory_pages
+                    ),
+                )));
+            }
+        }
+
+        for table in wasm_mod.tables.iter() {
+            if table.initial as u64 > limits.max_table_elements as u64 {
+                return Err(Error::Io(io::Error::new(
+                    io::ErrorKind::Other,
+                    format!(
+                        "rules require at least {} table element(s), but \
+                         pooling_limits allows only {}",
+                        table.initial, limits.max_table_elements
+                    ),
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
     fn process_imports(
         &mut self,
         src: &SourceCode,
@@ -262,11 +498,15 @@ impl Compiler {
             if let Some(module) =
                 modules::BUILTIN_MODULES.get(import.module_name.as_str())
             {
-                // ... if yes, add the module to the symbol table.
-                self.sym_tbl.insert(
-                    import.module_name.as_str(),
-                    TypeValue::Struct(Rc::new(module)),
-                );
+                // ... if yes, add the module to the symbol table of the
+                // namespace that's currently active, so that it's not
+                // visible from other namespaces.
+                self.namespaces[self.current_namespace_idx]
+                    .sym_tbl
+                    .insert(
+                        import.module_name.as_str(),
+                        TypeValue::Struct(Rc::new(module)),
+                    );
             } else {
                 // ... if no, that's an error.
                 return Err(Error::CompileError(
@@ -296,6 +536,69 @@ impl Default for Compiler {
     }
 }
 
+/// Cranelift optimization preset for [`Compiler::opt_level`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OptLevel {
+    /// Optimize for short compile times, at the expense of scan speed.
+    ///
+    /// Useful while iterating on a rule set, where `build()` runs once per
+    /// edit and every extra millisecond of Cranelift optimization is felt
+    /// directly by whoever is writing the rules.
+    FastCompile,
+
+    /// Optimize for scan speed, at the expense of compile time.
+    ///
+    /// Useful for long-lived processes that compile a rule set once and
+    /// then scan with it for a long time, e.g. a scanning daemon, where the
+    /// one-time Cranelift cost is amortized over many scans.
+    FastScan,
+}
+
+/// Limits for wasmtime's pooling instance allocator, used with
+/// [`Compiler::pooling_limits`].
+///
+/// The defaults are generous enough to not change behavior for typical
+/// rule sets, but [`Compiler::pooling_limits`] itself is opt-in, so callers
+/// who don't ask for it keep using the on-demand allocator unconditionally.
+#[derive(Clone, Copy, Debug)]
+pub struct PoolingLimits {
+    /// Maximum number of instances (and, equivalently, memories and
+    /// tables) that the pool can hand out concurrently.
+    pub max_instances: u32,
+
+    /// Maximum size of each instance's linear memory, in 64 KiB wasm pages.
+    pub max_memory_pages: u32,
+
+    /// Maximum number of elements in each instance's tables.
+    pub max_table_elements: u32,
+}
+
+impl Default for PoolingLimits {
+    fn default() -> Self {
+        Self {
+            max_instances: 1_000,
+            max_memory_pages: 1_000,
+            max_table_elements: 1_000,
+        }
+    }
+}
+
+/// A namespace created with [`Compiler::new_namespace`] (or the implicit
+/// `"default"` one every [`Compiler`] starts with).
+///
+/// Each namespace has its own symbol table, so a module imported in one
+/// namespace isn't visible from another, and its own slice of
+/// [`Compiler::rules`] identifiers, so the same rule name can be reused
+/// across namespaces without being flagged as a duplicate.
+struct Namespace {
+    /// The namespace's name, as an ID into the identifiers pool.
+    ident: IdentId,
+
+    /// Symbols visible from this namespace: imported modules and any other
+    /// namespace-local symbol.
+    sym_tbl: SymbolTable,
+}
+
 /// ID associated to each identifier in the identifiers pool.
 pub(crate) type IdentId = SymbolU32;
 
@@ -305,6 +608,11 @@ pub(crate) type PatternId = i32;
 /// ID associated to each rule.
 pub(crate) type RuleId = i32;
 
+/// ID associated to each namespace. This is the index of the namespace in
+/// [`Compiler::namespaces`], and later in [`CompiledRules`]'s own namespace
+/// list.
+pub(crate) type NamespaceId = u32;
+
 /// Structure that contains information and data structures required during the
 /// the current compilation process.
 struct Context<'a> {
@@ -372,13 +680,22 @@ pub struct CompiledRules {
     /// from the pool as a `&str`.
     ident_pool: StringInterner<DefaultBackend<IdentId>>,
 
-    /// WebAssembly module containing the code for all rule conditions.
-    wasm_mod: Module,
+    /// WebAssembly module containing the code for all rule conditions. This
+    /// is only present when the rules were produced by [`Compiler::build`];
+    /// rules loaded back with [`CompiledRules::deserialize`] only have the
+    /// already-compiled [`compiled_wasm_mod`](Self::compiled_wasm_mod) and
+    /// don't carry the original IR around.
+    wasm_mod: Option<Module>,
 
     /// WebAssembly module already compiled into native code for the current
     /// platform.
     compiled_wasm_mod: wasmtime::Module,
 
+    /// Name of every namespace that was active while compiling these rules,
+    /// as an ID into `ident_pool`. A [`NamespaceId`] is an index in this
+    /// vector; [`CompiledRule::namespace`] returns one of these.
+    namespaces: Vec<IdentId>,
+
     /// Vector containing all the compiled rules. A [`RuleID`] is an index
     /// in this vector.
     rules: Vec<CompiledRule>,
@@ -390,6 +707,44 @@ pub struct CompiledRules {
     patterns: Vec<Pattern>,
 }
 
+/// Magic number that identifies files produced by
+/// [`CompiledRules::serialize`].
+const SERIALIZATION_MAGIC: &[u8; 4] = b"YRX\0";
+
+/// Version of the serialization format used by [`CompiledRules::serialize`]
+/// and checked by [`CompiledRules::deserialize`].
+///
+/// This must be bumped every time the container format, the layout of
+/// [`CompiledRulesMeta`], or the wasmtime version we link against changes in
+/// a way that makes previously serialized files unreadable. Rejecting a
+/// mismatched version up-front keeps stale files away from the `unsafe` call
+/// to [`wasmtime::Module::deserialize`], which trusts its input completely.
+/// The check alone doesn't make that call sound; see the trust boundary
+/// note on [`CompiledRules::deserialize`].
+const SERIALIZATION_VERSION: u8 = 1;
+
+/// Upper bound on the size of the wasm blob accepted by
+/// [`CompiledRules::deserialize`].
+///
+/// The length prefix in a serialized file is attacker/corruption-controlled
+/// input read before anything else is validated, so it must be checked
+/// against some sane maximum before it's used to size an allocation.
+/// Compiled rule sets are native code for a handful of rule conditions, not
+/// arbitrary wasm programs, so 1 GiB is already far more than any real
+/// rule set should ever need.
+const MAX_SERIALIZED_WASM_SIZE: u64 = 1 << 30;
+
+/// The parts of [`CompiledRules`] that are plain data and can be serialized
+/// with `serde`, as opposed to [`CompiledRules::compiled_wasm_mod`], which
+/// has its own wasmtime-specific serialization.
+#[derive(Serialize, Deserialize)]
+struct CompiledRulesMeta {
+    ident_pool: StringInterner<DefaultBackend<IdentId>>,
+    namespaces: Vec<IdentId>,
+    rules: Vec<CompiledRule>,
+    patterns: Vec<Pattern>,
+}
+
 impl CompiledRules {
     /// Returns an slice with all the compiled rules.
     #[inline]
@@ -401,16 +756,183 @@ impl CompiledRules {
     pub(crate) fn compiled_wasm_mod(&self) -> &wasmtime::Module {
         &self.compiled_wasm_mod
     }
+
+    /// Returns the name of the namespace identified by `id`.
+    ///
+    /// Panics if `id` doesn't correspond to any namespace in this rule set.
+    #[inline]
+    pub fn namespace(&self, id: NamespaceId) -> &str {
+        self.ident_pool
+            .resolve(self.namespaces[id as usize])
+            .unwrap()
+    }
+
+    /// Serializes the compiled rules, writing them to `w`.
+    ///
+    /// The resulting bytes can be loaded back with
+    /// [`CompiledRules::deserialize`], without going through [`Compiler`]
+    /// again. This is the same idea behind `yarac` and the `-C` flag in
+    /// classic YARA: compile once, distribute the compiled artifact, and
+    /// let every scanner skip the compilation step entirely.
+    ///
+    /// The on-disk format is a small, self-describing container: a magic
+    /// number, a one-byte format version, the length of the native code as
+    /// a little-endian `u64`, the native code produced by
+    /// [`wasmtime::Module::serialize`], and finally the rest of the
+    /// [`CompiledRules`] data encoded with `bincode`.
+    pub fn serialize<W: Write>(&self, mut w: W) -> Result<(), Error> {
+        w.write_all(SERIALIZATION_MAGIC)?;
+        w.write_all(&[SERIALIZATION_VERSION])?;
+
+        let wasm_bytes = self.compiled_wasm_mod.serialize().map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                format!("failed to serialize wasm module: {}", err),
+            )
+        })?;
+
+        w.write_all(&(wasm_bytes.len() as u64).to_le_bytes())?;
+        w.write_all(wasm_bytes.as_slice())?;
+
+        let meta = CompiledRulesMeta {
+            ident_pool: self.ident_pool.clone(),
+            namespaces: self.namespaces.clone(),
+            rules: self.rules.clone(),
+            patterns: self.patterns.clone(),
+        };
+
+        bincode::serialize_into(&mut w, &meta).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                format!("failed to serialize rules: {}", err),
+            )
+        })?;
+
+        Ok(())
+    }
+
+    /// Deserializes compiled rules previously written by
+    /// [`CompiledRules::serialize`].
+    ///
+    /// # Trust boundary
+    ///
+    /// This function must only be fed bytes produced by
+    /// [`CompiledRules::serialize`] running with the *same* wasmtime version
+    /// on a *compatible* target (same architecture, same OS, same relevant
+    /// CPU features). The magic number and version tag let us reject
+    /// obviously wrong or stale input, but they are not a substitute for
+    /// that guarantee: [`wasmtime::Module::deserialize`] reconstructs a
+    /// native module directly from the bytes without re-verifying them, so
+    /// loading a file from an untrusted source or an incompatible build is
+    /// equivalent to running arbitrary native code. Treat serialized rules
+    /// the same way you'd treat a `.so`/`.dll` you didn't build yourself.
+    pub fn deserialize<R: Read>(mut r: R) -> Result<Self, Error> {
+        let mut magic = [0u8; 4];
+        r.read_exact(&mut magic)?;
+        if &magic != SERIALIZATION_MAGIC {
+            return Err(Error::Io(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a compiled YARA rules file",
+            )));
+        }
+
+        let mut version = [0u8; 1];
+        r.read_exact(&mut version)?;
+        if version[0] != SERIALIZATION_VERSION {
+            return Err(Error::Io(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "unsupported compiled rules format version {} (expected {})",
+                    version[0], SERIALIZATION_VERSION
+                ),
+            )));
+        }
+
+        let mut wasm_len_bytes = [0u8; 8];
+        r.read_exact(&mut wasm_len_bytes)?;
+        let wasm_len = u64::from_le_bytes(wasm_len_bytes);
+
+        if wasm_len > MAX_SERIALIZED_WASM_SIZE {
+            return Err(Error::Io(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "compiled rules file claims a {}-byte wasm module, \
+                     which exceeds the {}-byte maximum",
+                    wasm_len, MAX_SERIALIZED_WASM_SIZE
+                ),
+            )));
+        }
+
+        // Read at most `wasm_len` bytes instead of allocating `wasm_len`
+        // bytes up front and filling them with `read_exact`: a truncated
+        // file would otherwise only fail once the allocation is already
+        // sized to whatever the (possibly bogus) length prefix said.
+        let mut wasm_bytes = Vec::new();
+        (&mut r).take(wasm_len).read_to_end(&mut wasm_bytes)?;
+
+        if wasm_bytes.len() as u64 != wasm_len {
+            return Err(Error::Io(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "truncated compiled rules file",
+            )));
+        }
+
+        // SAFETY: the magic number and version check above only reject
+        // input that obviously wasn't written by `CompiledRules::serialize`
+        // with this format version. They don't prove where the bytes came
+        // from; that's the caller's responsibility, see the trust boundary
+        // note on this function's documentation.
+        let compiled_wasm_mod = unsafe {
+            wasmtime::Module::deserialize(&crate::wasm::ENGINE, &wasm_bytes)
+        }
+        .map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("failed to deserialize wasm module: {}", err),
+            )
+        })?;
+
+        let meta: CompiledRulesMeta = bincode::deserialize_from(&mut r)
+            .map_err(|err| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("failed to deserialize rules: {}", err),
+                )
+            })?;
+
+        Ok(CompiledRules {
+            compiled_wasm_mod,
+            wasm_mod: None,
+            ident_pool: meta.ident_pool,
+            namespaces: meta.namespaces,
+            rules: meta.rules,
+            patterns: meta.patterns,
+        })
+    }
 }
 
 /// A compiled rule.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct CompiledRule {
     /// The ID of the rule identifier in the identifiers pool.
     pub(crate) ident: IdentId,
 
+    /// The namespace this rule belongs to. Use
+    /// [`CompiledRules::namespace`] to resolve it to a name.
+    pub(crate) namespace: NamespaceId,
+
     /// Vector with all the patterns defined by this rule.
     patterns: Vec<(IdentId, PatternId)>,
 }
 
+impl CompiledRule {
+    /// Returns the ID of the namespace this rule belongs to.
+    #[inline]
+    pub fn namespace(&self) -> NamespaceId {
+        self.namespace
+    }
+}
+
 /// A pattern in the compiled rules.
+#[derive(Clone, Serialize, Deserialize)]
 struct Pattern {}
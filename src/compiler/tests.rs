@@ -0,0 +1,122 @@
+use crate::compiler::{CompiledRules, Compiler, OptLevel, PoolingLimits};
+
+#[test]
+fn serialize_deserialize_round_trip() {
+    let rules = Compiler::new()
+        .add_source(r#"rule t { condition: true }"#)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let mut buf = Vec::new();
+    rules.serialize(&mut buf).unwrap();
+
+    let rules = CompiledRules::deserialize(buf.as_slice()).unwrap();
+
+    let mut scanner = crate::scanner::Scanner::new(&rules);
+    assert_eq!(scanner.scan(&[]).num_matching_rules(), 1);
+}
+
+#[test]
+fn deserialize_rejects_truncated_input() {
+    let rules = Compiler::new()
+        .add_source(r#"rule t { condition: true }"#)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let mut buf = Vec::new();
+    rules.serialize(&mut buf).unwrap();
+
+    // Cut the buffer in half. The wasm module makes up most of the
+    // serialized bytes, so the cut lands inside it and the wasm length
+    // prefix promises more data than is actually there.
+    buf.truncate(buf.len() / 2);
+
+    assert!(CompiledRules::deserialize(buf.as_slice()).is_err());
+}
+
+#[test]
+fn opt_level_presets_still_build_working_rules() {
+    for opt_level in [OptLevel::FastCompile, OptLevel::FastScan] {
+        let rules = Compiler::new()
+            .opt_level(opt_level)
+            .add_source(r#"rule t { condition: true }"#)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let mut scanner = crate::scanner::Scanner::new(&rules);
+        assert_eq!(scanner.scan(&[]).num_matching_rules(), 1);
+    }
+}
+
+#[test]
+fn pooling_limits_defaults_still_build_working_rules() {
+    let rules = Compiler::new()
+        .pooling_limits(PoolingLimits::default())
+        .add_source(r#"rule t { condition: true }"#)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let mut scanner = crate::scanner::Scanner::new(&rules);
+    assert_eq!(scanner.scan(&[]).num_matching_rules(), 1);
+}
+
+#[test]
+fn pooling_limits_too_small_is_a_build_error_not_a_trap() {
+    let result = Compiler::new()
+        .pooling_limits(PoolingLimits {
+            max_instances: 1,
+            max_memory_pages: 0,
+            max_table_elements: 0,
+        })
+        .add_source(r#"rule t { condition: true }"#)
+        .unwrap()
+        .build();
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn same_rule_name_in_different_namespaces_does_not_collide() {
+    let rules = Compiler::new()
+        .add_source(r#"rule t { condition: true }"#)
+        .unwrap()
+        .new_namespace("other")
+        .add_source(r#"rule t { condition: true }"#)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    assert_eq!(rules.rules().len(), 2);
+    assert_ne!(
+        rules.rules()[0].namespace(),
+        rules.rules()[1].namespace()
+    );
+}
+
+#[test]
+fn duplicate_rule_name_in_same_namespace_is_an_error() {
+    let result = Compiler::new()
+        .add_source(r#"rule t { condition: true }"#)
+        .unwrap()
+        .add_source(r#"rule t { condition: true }"#);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn imports_dont_leak_across_namespaces() {
+    let result = Compiler::new()
+        .add_source(
+            r#"import "string"
+               rule t { condition: string.length("a") == 1 }"#,
+        )
+        .unwrap()
+        .new_namespace("other")
+        .add_source(r#"rule u { condition: string.length("a") == 1 }"#);
+
+    assert!(result.is_err());
+}